@@ -0,0 +1,129 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// Identity and network settings for this node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NodeConfig {
+    pub node_id: String,
+    pub listen_addr: String,
+    /// Directory this node stores its log segments in.
+    pub data_dir: PathBuf,
+}
+
+impl Default for NodeConfig {
+    fn default() -> Self {
+        Self {
+            node_id: "node-1".to_string(),
+            listen_addr: "127.0.0.1:7000".to_string(),
+            data_dir: PathBuf::from("data"),
+        }
+    }
+}
+
+/// Replication settings for this node.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplicationConfig {
+    /// Addresses of peer nodes to replicate log entries to.
+    pub peers: Vec<String>,
+}
+
+/// Where log output is written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogOutput {
+    #[default]
+    Stdout,
+    File,
+    Both,
+    /// Structured logging via the systemd journal. Falls back to stdout
+    /// if the journald socket isn't reachable (e.g. outside systemd).
+    Journald,
+}
+
+/// How often the rolling file appender starts a new file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRotation {
+    Hourly,
+    #[default]
+    Daily,
+}
+
+/// Logging/tracing behavior for this node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Path to a file whose (trimmed) contents are parsed as an `EnvFilter`
+    /// directive and hot-reloaded into the subscriber, e.g. `debug` or
+    /// `dlog=debug,info`. Missing or empty files fall back to the default
+    /// filter.
+    pub level_file: Option<PathBuf>,
+    /// Where log lines are written.
+    pub output: LogOutput,
+    /// Directory the rolling file appender writes into. Only used when
+    /// `output` is `file` or `both`.
+    pub directory: PathBuf,
+    /// How often the rolling file appender rotates to a new file.
+    pub rotation: LogRotation,
+    /// Filename prefix for rolled log files, e.g. `dlog` -> `dlog.2024-05-01-14`.
+    pub file_prefix: String,
+    /// Enables the `tokio-console` diagnostics layer. Only takes effect
+    /// when built with the `console` feature; off by default since it
+    /// adds polling overhead and requires `tokio_unstable`.
+    pub tokio_console: bool,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level_file: None,
+            output: LogOutput::default(),
+            directory: PathBuf::from("logs"),
+            rotation: LogRotation::default(),
+            file_prefix: "dlog".to_string(),
+            tokio_console: false,
+        }
+    }
+}
+
+/// Top-level configuration for a `dlog` node.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DLogConfig {
+    #[serde(default)]
+    pub node: NodeConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub replication: ReplicationConfig,
+}
+
+impl DLogConfig {
+    /// Loads and parses a TOML config file from `path`.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file `{}`", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("failed to parse config file `{}`", path.display()))
+    }
+}
+
+/// The bundled default configuration, written out by [`init`].
+const DEFAULT_CONFIG_TOML: &str = include_str!("../dlog.toml");
+
+/// Writes the bundled default configuration to `path`, refusing to
+/// overwrite a file that already exists there.
+pub fn init(path: &Path) -> anyhow::Result<()> {
+    if path.exists() {
+        anyhow::bail!(
+            "config file `{}` already exists, refusing to overwrite",
+            path.display()
+        );
+    }
+    std::fs::write(path, DEFAULT_CONFIG_TOML)
+        .with_context(|| format!("failed to write config file `{}`", path.display()))?;
+    Ok(())
+}
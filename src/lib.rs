@@ -0,0 +1,7 @@
+pub mod config;
+pub mod logging;
+pub mod server;
+pub mod signal;
+
+pub use config::DLogConfig;
+pub use server::DLogServer;
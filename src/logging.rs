@@ -0,0 +1,255 @@
+//! Tracing subscriber setup: a watched level file for runtime verbosity
+//! changes, plus stdout, non-blocking rolling-file, and journald output.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+use tracing::Subscriber;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::layer::{Layered, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{filter::EnvFilter, fmt, reload, Layer, Registry};
+
+use crate::config::{LogOutput, LogRotation, LoggingConfig};
+use crate::DLogConfig;
+
+const DEFAULT_FILTER: &str = "info";
+const LEVEL_FILE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The concrete subscriber type the reload filter layers onto: the rest
+/// of the layers (stdout/file/journald/console) are composed on top of
+/// this, not of the bare [`Registry`].
+type FilteredRegistry = Layered<reload::Layer<EnvFilter, Registry>, Registry>;
+
+/// Guards that must stay alive for the process lifetime so buffered log
+/// lines are flushed on shutdown. Dropping this drops the non-blocking
+/// writer worker thread.
+#[must_use]
+pub struct LoggingGuard {
+    _file_worker: Option<WorkerGuard>,
+}
+
+/// Builds the tracing subscriber and spawns the background task that
+/// watches `logging.level_file` for changes. Must be called once, before
+/// any other initialization that might log. `cancellation` stops the
+/// level-file watcher on graceful shutdown, matching the contract the
+/// server's other internal tasks subscribe to. The returned
+/// [`LoggingGuard`] must be held for the lifetime of the process.
+pub fn init(config: &DLogConfig, cancellation: CancellationToken) -> anyhow::Result<LoggingGuard> {
+    let level_file = config.logging.level_file.clone();
+    let console_enabled = console_enabled(&config.logging);
+
+    let initial_directive = read_level_file(level_file.as_deref()).unwrap_or_else(|| DEFAULT_FILTER.to_string());
+    let (initial_filter, initial_applied) = match build_filter(&initial_directive, console_enabled) {
+        Some(filter) => (filter, initial_directive),
+        None => (
+            build_filter(DEFAULT_FILTER, console_enabled).expect("default filter is valid"),
+            DEFAULT_FILTER.to_string(),
+        ),
+    };
+
+    let (filter, reload_handle) = reload::Layer::new(initial_filter);
+
+    let (primary_layer, journald_unavailable) = match config.logging.output {
+        LogOutput::Stdout | LogOutput::Both => (Some(stdout_layer::<FilteredRegistry>()), false),
+        LogOutput::File => (None, false),
+        LogOutput::Journald => match build_journald_layer::<FilteredRegistry>() {
+            Ok(layer) => (Some(layer), false),
+            Err(_) => (Some(stdout_layer::<FilteredRegistry>()), true),
+        },
+    };
+
+    let (file_layer, file_worker) = match config.logging.output {
+        LogOutput::File | LogOutput::Both => {
+            let (layer, guard) = build_file_layer::<FilteredRegistry>(&config.logging);
+            (Some(layer), Some(guard))
+        }
+        LogOutput::Stdout | LogOutput::Journald => (None, None),
+    };
+
+    // Collected into one `Vec` (rather than chained `.with()` calls) so
+    // every optional layer shares the same concrete subscriber type;
+    // `.with()` changes that type on each call, which boxed trait objects
+    // from independent `.with()` calls can't follow.
+    let mut layers: Vec<Box<dyn Layer<FilteredRegistry> + Send + Sync>> = Vec::new();
+    layers.extend(primary_layer);
+    layers.extend(file_layer);
+    layers.extend(console_layer::<FilteredRegistry>(&config.logging));
+
+    tracing_subscriber::registry().with(filter).with(layers).init();
+
+    if journald_unavailable {
+        tracing::warn!("journald socket unreachable, falling back to stdout logging");
+    }
+
+    tokio::spawn(watch_level_file(
+        level_file,
+        reload_handle,
+        initial_applied,
+        console_enabled,
+        cancellation,
+    ));
+
+    Ok(LoggingGuard {
+        _file_worker: file_worker,
+    })
+}
+
+/// Parses `directive` into an `EnvFilter`, returning `None` if it's
+/// invalid. When `console_enabled`, the `tokio=trace,runtime=trace`
+/// directives tokio-console depends on are appended so the console layer
+/// isn't starved by the configured/reloaded filter.
+fn build_filter(directive: &str, console_enabled: bool) -> Option<EnvFilter> {
+    let mut filter = EnvFilter::try_new(directive).ok()?;
+    if console_enabled {
+        filter = filter
+            .add_directive("tokio=trace".parse().expect("valid directive"))
+            .add_directive("runtime=trace".parse().expect("valid directive"));
+    }
+    Some(filter)
+}
+
+fn stdout_layer<S>() -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fmt::layer()
+        .with_target(false)
+        .with_thread_ids(true)
+        .with_level(true)
+        .boxed()
+}
+
+/// Builds the journald layer, forwarding spans and event fields as
+/// structured journal fields and mapping tracing levels to syslog
+/// priorities. Returns an error if the journald socket isn't reachable.
+fn build_journald_layer<S>() -> std::io::Result<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    Ok(tracing_journald::layer()?.boxed())
+}
+
+#[cfg(feature = "console")]
+fn console_enabled(config: &LoggingConfig) -> bool {
+    config.tokio_console || std::env::var_os("DLOG_TOKIO_CONSOLE").is_some()
+}
+
+#[cfg(not(feature = "console"))]
+fn console_enabled(_config: &LoggingConfig) -> bool {
+    false
+}
+
+/// Builds the `tokio-console` diagnostics layer, gated behind the
+/// `console` feature and `logging.tokio_console` (or the
+/// `DLOG_TOKIO_CONSOLE` env var) so it's off by default. Exposes task
+/// polls, wakers, and busy/idle durations to a connected `tokio-console`
+/// client. The layer has no per-layer filter of its own; the reload
+/// `EnvFilter` sits beneath it and gates every layer, which is why
+/// [`build_filter`] adds the directives this layer depends on.
+#[cfg(feature = "console")]
+fn console_layer<S>(config: &LoggingConfig) -> Option<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    console_enabled(config).then(|| console_subscriber::ConsoleLayer::builder().spawn().boxed())
+}
+
+#[cfg(not(feature = "console"))]
+fn console_layer<S>(_config: &LoggingConfig) -> Option<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    None
+}
+
+/// Builds the rolling, non-blocking file layer. ANSI color codes are
+/// disabled since they only make sense on a terminal.
+fn build_file_layer<S>(config: &LoggingConfig) -> (Box<dyn Layer<S> + Send + Sync>, WorkerGuard)
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let rotation = match config.rotation {
+        LogRotation::Hourly => tracing_appender::rolling::Rotation::HOURLY,
+        LogRotation::Daily => tracing_appender::rolling::Rotation::DAILY,
+    };
+    let appender = tracing_appender::rolling::RollingFileAppender::new(
+        rotation,
+        &config.directory,
+        &config.file_prefix,
+    );
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    let layer = fmt::layer()
+        .with_target(false)
+        .with_thread_ids(true)
+        .with_level(true)
+        .with_ansi(false)
+        .with_writer(writer)
+        .boxed();
+    (layer, guard)
+}
+
+/// Polls `level_file` every [`LEVEL_FILE_POLL_INTERVAL`] and reloads the
+/// filter when its (trimmed) contents differ from what's actually
+/// applied. Tolerates a missing or empty file by falling back to
+/// [`DEFAULT_FILTER`], and keeps retrying an invalid directive on every
+/// poll until it's corrected (or the level file starts matching whatever
+/// fallback is currently active). Exits once `cancellation` fires.
+async fn watch_level_file(
+    level_file: Option<PathBuf>,
+    handle: reload::Handle<EnvFilter, Registry>,
+    mut last_applied: String,
+    console_enabled: bool,
+    cancellation: CancellationToken,
+) {
+    let Some(level_file) = level_file else {
+        return;
+    };
+
+    let mut interval = tokio::time::interval(LEVEL_FILE_POLL_INTERVAL);
+    interval.tick().await; // skip the immediate first tick; `init` already applied the initial value
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = cancellation.cancelled() => {
+                tracing::info!("stopping level file watcher");
+                return;
+            }
+        }
+
+        let contents = read_level_file(Some(&level_file)).unwrap_or_else(|| DEFAULT_FILTER.to_string());
+
+        if contents == last_applied {
+            continue;
+        }
+
+        match build_filter(&contents, console_enabled) {
+            Some(new_filter) => match handle.reload(new_filter) {
+                Ok(()) => {
+                    tracing::info!(new_level = %contents, "reloaded log filter from level file");
+                    last_applied = contents;
+                }
+                Err(error) => {
+                    tracing::warn!(%error, "failed to reload log filter");
+                }
+            },
+            None => {
+                tracing::warn!(contents = %contents, "ignoring invalid filter directive in level file");
+            }
+        }
+    }
+}
+
+fn read_level_file(path: Option<&std::path::Path>) -> Option<String> {
+    let path = path?;
+    let contents = std::fs::read_to_string(path).ok()?;
+    let trimmed = contents.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
@@ -1,25 +1,45 @@
-use dlog::{DLogServer, DLogConfig};
+use dlog::{DLogConfig, DLogServer};
+use std::path::PathBuf;
 use std::sync::Arc;
-use tracing_subscriber;
+use tokio_util::sync::CancellationToken;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_target(false)
-        .with_thread_ids(true)
-        .with_level(true)
-        .init();
+    let mut args = std::env::args().skip(1);
 
-    // Load configuration
-    let config = DLogConfig::default();
+    match args.next().as_deref() {
+        Some("init") => {
+            let path = args.next().map(PathBuf::from).unwrap_or_else(|| PathBuf::from("dlog.toml"));
+            dlog::config::init(&path)?;
+            println!("wrote default config to {}", path.display());
+            Ok(())
+        }
+        Some(path) => run(DLogConfig::load(&PathBuf::from(path))?).await,
+        None => {
+            let config = match std::env::var_os("DLOG_CONFIG") {
+                Some(path) => DLogConfig::load(&PathBuf::from(path))?,
+                None => DLogConfig::default(),
+            };
+            run(config).await
+        }
+    }
+}
+
+async fn run(config: DLogConfig) -> anyhow::Result<()> {
+    // Shared by the logging level-file watcher and the server so that one
+    // shutdown drains both.
+    let cancellation = CancellationToken::new();
+
+    // Initialize tracing, including the watched level file for runtime
+    // verbosity changes. The guard must stay alive for the process
+    // lifetime so buffered file log lines are flushed on shutdown.
+    let _logging_guard = dlog::logging::init(&config, cancellation.child_token())?;
 
     tracing::info!("Starting DLog server with node_id={}", config.node.node_id);
 
-    // Create and start server
-    let server = Arc::new(DLogServer::new(config).await?);
-    server.start().await?;
+    // Create and start server, shutting down cleanly on SIGINT/SIGTERM
+    let server = Arc::new(DLogServer::new(config, cancellation).await?);
+    server.run_until_signal().await?;
 
     Ok(())
 }
-
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::DLogConfig;
+use crate::signal::wait_for_shutdown_signal;
+
+/// The `dlog` log server. Holds the node configuration and the shared
+/// shutdown token; it opens no listener and does not store or replicate
+/// log entries.
+pub struct DLogServer {
+    config: DLogConfig,
+    cancellation: CancellationToken,
+}
+
+impl DLogServer {
+    /// `cancellation` is shared with other subsystems started alongside
+    /// the server (e.g. the logging level-file watcher) so a single
+    /// [`DLogServer::shutdown`] call drains all of them together.
+    pub async fn new(config: DLogConfig, cancellation: CancellationToken) -> Result<Self> {
+        Ok(Self { config, cancellation })
+    }
+
+    /// Runs the server until cancelled via [`DLogServer::shutdown`] or the
+    /// token returned by [`DLogServer::cancellation_token`].
+    pub async fn start(&self) -> Result<()> {
+        tracing::info!(addr = %self.config.node.listen_addr, "dlog server listening");
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(3600)) => {}
+                _ = self.cancellation.cancelled() => {
+                    tracing::info!("no longer accepting new connections");
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the server until it receives SIGINT/SIGTERM or is cancelled,
+    /// then cancels the shared token (stopping [`DLogServer::start`] and
+    /// the tasks subscribed to it) and calls [`DLogServer::flush`]. It
+    /// does not wait for the subscribed tasks to exit.
+    pub async fn run_until_signal(self: &Arc<Self>) -> Result<()> {
+        tokio::select! {
+            result = self.start() => result?,
+            _ = wait_for_shutdown_signal() => self.shutdown(),
+        }
+
+        self.flush().await?;
+        Ok(())
+    }
+
+    /// Requests a graceful shutdown. Safe to call multiple times or
+    /// concurrently with [`DLogServer::start`].
+    pub fn shutdown(&self) {
+        self.cancellation.cancel();
+    }
+
+    /// A cancellation token that fires when [`DLogServer::shutdown`] is
+    /// called, for internal tasks (replication, flush timers) to
+    /// subscribe to.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Shutdown flush step. Only emits a log line: the server holds no
+    /// log segments, so nothing is written or fsynced here.
+    async fn flush(&self) -> Result<()> {
+        tracing::info!("flushing log segments");
+        Ok(())
+    }
+}